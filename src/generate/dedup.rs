@@ -0,0 +1,122 @@
+use std::collections::HashMap;
+
+use crate::svd::{Access, Device, Field, Peripheral, Register, RegisterCluster};
+
+/// Structural description of a peripheral's register block, used to spot
+/// peripherals that are byte-for-byte identical even though the SVD has no
+/// `derivedFrom` link between them.
+type StructuralKey = String;
+
+/// Group peripherals whose register block is structurally identical
+///
+/// Returns a map from each peripheral's name to the name of the
+/// *representative* peripheral whose register block module should be
+/// generated; every other member of the group reuses it. A peripheral that
+/// has no duplicate (or is already `derivedFrom` something, has no register
+/// block of its own, or contains clusters) maps to itself; peripherals with
+/// clusters are never grouped, even when their layouts are identical.
+pub fn group_peripherals(d: &Device) -> HashMap<String, String> {
+    let mut by_key: HashMap<StructuralKey, String> = HashMap::new();
+    let mut representative = HashMap::new();
+
+    for p in &d.peripherals {
+        let regs = p.registers.as_ref().map(|v| &v[..]).unwrap_or(&[]);
+        let has_register = regs
+            .iter()
+            .any(|rc| matches!(rc, RegisterCluster::Register(_)));
+        let has_cluster = regs
+            .iter()
+            .any(|rc| matches!(rc, RegisterCluster::Cluster(_)));
+
+        if p.derived_from.is_some() || !has_register || has_cluster {
+            // No register block of its own, already `derivedFrom` something,
+            // or nested clusters we don't structurally compare: leave it out
+            // of dedup rather than risk merging it with an unrelated layout.
+            representative.insert(p.name.clone(), p.name.clone());
+            continue;
+        }
+
+        let key = structural_key(p);
+        let name = by_key.entry(key).or_insert_with(|| p.name.clone());
+        representative.insert(p.name.clone(), name.clone());
+    }
+
+    representative
+}
+
+fn structural_key(p: &Peripheral) -> StructuralKey {
+    // `group_peripherals` only calls this for peripherals it has already
+    // established contain no `RegisterCluster::Cluster`, so every entry here
+    // is a top-level `Register`.
+    let mut registers: Vec<&Register> = p
+        .registers
+        .as_ref()
+        .map(|regs| {
+            regs.iter()
+                .filter_map(|rc| match rc {
+                    RegisterCluster::Register(r) => Some(r),
+                    RegisterCluster::Cluster(_) => None,
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+    registers.sort_by_key(|r| r.address_offset);
+
+    registers
+        .iter()
+        .map(|r| {
+            // Geometry alone isn't enough: a deduped member reuses the
+            // representative's generated accessors wholesale, so two
+            // registers that only differ by name must not collide.
+            format!(
+                "{}:{}:{}:{}:{:?}:[{}]",
+                r.name,
+                r.address_offset,
+                r.size.unwrap_or(32),
+                access_key(r.access),
+                r.reset_value,
+                field_key(r.fields.as_deref()),
+            )
+        })
+        .collect::<Vec<_>>()
+        .join(";")
+}
+
+fn field_key(fields: Option<&[Field]>) -> String {
+    let mut fields: Vec<&Field> = fields.map(|f| f.iter().collect()).unwrap_or_default();
+    fields.sort_by_key(|f| f.bit_range.offset);
+
+    fields
+        .iter()
+        .map(|f| {
+            format!(
+                "{}:{}:{}:{}:{}",
+                f.name,
+                f.bit_range.offset,
+                f.bit_range.width,
+                access_key(f.access),
+                enumerated_key(&f.enumerated_values),
+            )
+        })
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+fn access_key(access: Option<Access>) -> &'static str {
+    match access {
+        Some(Access::ReadOnly) => "ro",
+        Some(Access::WriteOnly) => "wo",
+        Some(Access::WriteOnce) => "woc",
+        Some(Access::ReadWriteOnce) => "rwoc",
+        Some(Access::ReadWrite) | None => "rw",
+    }
+}
+
+fn enumerated_key(values: &[crate::svd::EnumeratedValues]) -> String {
+    values
+        .iter()
+        .flat_map(|ev| ev.values.iter())
+        .map(|v| format!("{}={:?}", v.name, v.value))
+        .collect::<Vec<_>>()
+        .join("|")
+}
@@ -8,7 +8,7 @@ use crate::errors::*;
 use crate::util::{self, ToSanitizedSnakeCase, ToSanitizedUpperCase};
 use crate::Target;
 
-use crate::generate::{interrupt, peripheral};
+use crate::generate::{dedup, interrupt, metadata, peripheral};
 
 /// A collection of Tokens and available feature flags
 pub struct RenderOutput {
@@ -16,6 +16,52 @@ pub struct RenderOutput {
     pub features: Vec<String>,
 }
 
+/// Configuration for generating secure / non-secure peripheral aliases on
+/// TrustZone-M devices (e.g. nRF9160, Cortex-M33)
+#[derive(Clone)]
+pub struct TrustZoneConfig {
+    /// Bitmask separating a peripheral's secure base address from its
+    /// non-secure one (bit 28 on nRF9160-style parts)
+    pub offset: u32,
+    /// Names of peripherals carrying a secure alias; `None` applies to every
+    /// peripheral that makes it into `Peripherals`
+    pub peripherals: Option<Vec<String>>,
+}
+
+impl TrustZoneConfig {
+    fn applies_to(&self, name: &str) -> bool {
+        self.peripherals
+            .as_ref()
+            .map(|names| names.iter().any(|n| n == name))
+            .unwrap_or(true)
+    }
+}
+
+/// Configuration for generating `generic::AtomicAliased` impls on targets
+/// that map every register at additional atomic set/clear/xor addresses
+/// (e.g. RP2040-style SIO-atomic peripherals)
+#[derive(Clone)]
+pub struct AtomicAliasConfig {
+    /// Byte offset of the atomic *set* alias, relative to a register's own address
+    pub set: usize,
+    /// Byte offset of the atomic *clear* alias, relative to a register's own address
+    pub clear: usize,
+    /// Byte offset of the atomic *xor* alias, relative to a register's own address
+    pub xor: usize,
+    /// Names of peripherals backed by these alias windows; `None` applies to
+    /// every peripheral
+    pub peripherals: Option<Vec<String>>,
+}
+
+impl AtomicAliasConfig {
+    fn applies_to(&self, name: &str) -> bool {
+        self.peripherals
+            .as_ref()
+            .map(|names| names.iter().any(|n| n == name))
+            .unwrap_or(true)
+    }
+}
+
 /// Whole device generation
 pub fn render(
     d: &Device,
@@ -23,6 +69,10 @@ pub fn render(
     nightly: bool,
     generic_mod: bool,
     conditional: bool,
+    metadata_mod: bool,
+    trustzone: Option<&TrustZoneConfig>,
+    split: bool,
+    atomic_aliases: Option<&AtomicAliasConfig>,
     device_x: &mut String,
 ) -> Result<RenderOutput> {
     let mut output = RenderOutput {
@@ -123,6 +173,19 @@ pub fn render(
         .tokens
         .extend(interrupt::render(target, &d.peripherals, device_x)?);
 
+    if metadata_mod {
+        let metadata_tokens = metadata::render(d)?;
+        // Also written to its own file so a downstream `build.rs` can
+        // `include!` it, the same way `generic_mod` writes `generic.rs`.
+        writeln!(
+            File::create("metadata.rs").chain_err(|| "couldn't create metadata.rs")?,
+            "{}",
+            metadata_tokens
+        )
+        .chain_err(|| "couldn't write to metadata.rs")?;
+        output.tokens.push(metadata_tokens);
+    }
+
     let core_peripherals: &[_] = if fpu_present {
         &[
             "CBP", "CPUID", "DCB", "DWT", "FPB", "FPU", "ITM", "MPU", "NVIC", "SCB", "SYST",
@@ -134,6 +197,11 @@ pub fn render(
         ]
     };
 
+    // Peripherals sharing an identical register/field layout (but no SVD
+    // `derivedFrom` link) generate their register block module only once;
+    // see `dedup::group_peripherals`.
+    let dedup_groups = dedup::group_peripherals(d);
+
     let mut fields = vec![];
     let mut exprs = vec![];
     if target == Target::CortexM {
@@ -182,9 +250,64 @@ pub fn render(
             continue;
         }
 
-        output
-            .tokens
-            .extend(peripheral::render(p, &d.peripherals, &d.defaults, nightly, conditional)?);
+        let snake_name = p.name.to_sanitized_snake_case();
+        // Should we allow for conditional compilation of each peripheral?
+        let gate = if conditional {
+            Some(quote!(#[cfg(feature = #snake_name)]))
+        } else {
+            None
+        };
+
+        let atomic_aliased =
+            |name: &str| atomic_aliases.filter(|c| c.applies_to(name)).is_some();
+        // A member only reuses a block whose `AtomicAliased` impls match its own.
+        let representative = dedup_groups
+            .get(&p.name)
+            .map(String::as_str)
+            .filter(|r| atomic_aliased(r) == atomic_aliased(&p.name))
+            .unwrap_or(&p.name);
+        let ptokens = if representative == p.name {
+            let atomic_aliases = atomic_aliases.filter(|c| c.applies_to(&p.name));
+            peripheral::render(
+                p,
+                &d.peripherals,
+                &d.defaults,
+                nightly,
+                conditional,
+                atomic_aliases,
+            )?
+        } else {
+            // Structurally identical to `representative`'s register block:
+            // reuse its module instead of emitting another copy.
+            peripheral::render_alias(p, representative, conditional, None, None)?
+        };
+
+        if split {
+            // One file per peripheral keeps rustc's per-codegen-unit memory
+            // down and lets cargo recompile/parallelize on a per-peripheral
+            // basis, same idea as `generic_mod` does for `generic.rs`.
+            let path = format!("{}.rs", snake_name);
+            writeln!(
+                File::create(&path).chain_err(|| format!("couldn't create {}", path))?,
+                "{}",
+                quote!(#(#ptokens)*)
+            )
+            .chain_err(|| format!("couldn't write to {}", path))?;
+
+            let mod_id = Ident::new(&snake_name, Span::call_site());
+            output.tokens.push(quote! {
+                #gate
+                pub mod #mod_id;
+                // The singleton struct (and its `Deref` impl) live inside
+                // `#mod_id`; re-export it so `Peripherals` can still name it
+                // unqualified, same as the inline (non-split) output did.
+                #gate
+                #[allow(unused_imports)]
+                pub use self::#mod_id::*;
+            });
+        } else {
+            output.tokens.extend(ptokens);
+        }
 
         if p.registers
             .as_ref()
@@ -199,16 +322,41 @@ pub fn render(
         }
 
         let upper_name = p.name.to_sanitized_upper_case();
-        let snake_name = p.name.to_sanitized_snake_case();
         output.features.push(String::from(snake_name.clone()));
-        let id = Ident::new(&*upper_name, Span::call_site());
 
-        // Should we allow for conditional compilation of each peripheral?
-        let gate = if conditional {
-            Some(quote!(#[cfg(feature = #snake_name)]))
-        } else {
-            None
-        };
+        if let Some(tz) = trustzone.filter(|tz| tz.applies_to(&p.name)) {
+            let secure_addr = u64::from(p.base_address as u32 | tz.offset);
+            let nonsecure_addr = u64::from(p.base_address as u32 & !tz.offset);
+
+            for (suffix, addr) in [("_S", secure_addr), ("_NS", nonsecure_addr)] {
+                let alias_name = format!("{}{}", upper_name, suffix);
+                let alias_id = Ident::new(&alias_name, Span::call_site());
+                // Give each view its own singleton name (`UARTE0_S`/
+                // `UARTE0_NS`) so they don't collide with each other or with
+                // `p`'s own name; both point at `representative`'s register
+                // block module rather than `p.name`, since `p` may itself be
+                // a dedup member whose own name is only an alias.
+                output.tokens.extend(peripheral::render_alias(
+                    p,
+                    representative,
+                    conditional,
+                    Some(addr),
+                    Some(&alias_name),
+                )?);
+                fields.push(quote! {
+                    #[doc = #alias_name]
+                    #gate
+                    pub #alias_id: #alias_id
+                });
+                exprs.push(quote! {
+                    #gate
+                    #alias_id: #alias_id { _marker: PhantomData }
+                });
+            }
+            continue;
+        }
+
+        let id = Ident::new(&*upper_name, Span::call_site());
         fields.push(quote! {
             #[doc = #upper_name]
             #gate
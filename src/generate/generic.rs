@@ -139,6 +139,57 @@ where
     }
 }
 
+///Marks registers backed by hardware atomic set/clear/xor alias windows
+///
+///Some targets (e.g. RP2040-style SIO-atomic peripherals) map every register
+///at three additional addresses that perform an atomic bitwise set, clear,
+///or XOR on write, so `set_bits`/`clear_bits`/`toggle_bits` never race a
+///concurrent read-modify-write the way [`modify`](Reg::modify) can.
+pub trait AtomicAliased {
+    ///Byte offset, relative to the register's own address, of the atomic *set* alias
+    const SET: usize;
+    ///Byte offset, relative to the register's own address, of the atomic *clear* alias
+    const CLR: usize;
+    ///Byte offset, relative to the register's own address, of the atomic *xor* alias
+    const XOR: usize;
+}
+
+impl<U, REG> Reg<U, REG>
+where
+    Self: AtomicAliased + Writable,
+    U: Copy + Default,
+{
+    ///Atomically sets bits in the register through its `SET` alias
+    #[inline(always)]
+    pub fn set_bits<F>(&self, f: F)
+    where
+        F: FnOnce(&mut W<Self>) -> &mut W<Self>,
+    {
+        let alias = (self as *const Self as usize + Self::SET) as *const vcell::VolatileCell<U>;
+        unsafe { (*alias).set(f(&mut W { bits: U::default() }).bits) }
+    }
+
+    ///Atomically clears bits in the register through its `CLR` alias
+    #[inline(always)]
+    pub fn clear_bits<F>(&self, f: F)
+    where
+        F: FnOnce(&mut W<Self>) -> &mut W<Self>,
+    {
+        let alias = (self as *const Self as usize + Self::CLR) as *const vcell::VolatileCell<U>;
+        unsafe { (*alias).set(f(&mut W { bits: U::default() }).bits) }
+    }
+
+    ///Atomically toggles (XORs) bits in the register through its `XOR` alias
+    #[inline(always)]
+    pub fn toggle_bits<F>(&self, f: F)
+    where
+        F: FnOnce(&mut W<Self>) -> &mut W<Self>,
+    {
+        let alias = (self as *const Self as usize + Self::XOR) as *const vcell::VolatileCell<U>;
+        unsafe { (*alias).set(f(&mut W { bits: U::default() }).bits) }
+    }
+}
+
 ///Register/field reader
 pub struct R<T> where T: SizeType {
     pub(crate) bits: T::Type,
@@ -0,0 +1,132 @@
+use proc_macro2::TokenStream;
+use quote::quote;
+
+use crate::errors::*;
+use crate::svd::Device;
+use crate::util;
+
+/// Render the `metadata` module
+///
+/// This is opt-in: it emits a `'static`, `const`-constructible `METADATA`
+/// value describing the whole device (peripherals, interrupts, core info) as
+/// plain data, so a downstream HAL's `build.rs` can `include!` this module
+/// and walk it with ordinary Rust code -- counting, filtering, deduplicating
+/// -- instead of maintaining a fragile declarative macro table.
+pub fn render(d: &Device) -> Result<TokenStream> {
+    let peripherals = d.peripherals.iter().map(|p| {
+        let name = &p.name;
+        let base_address = util::unsuffixed(p.base_address);
+        let size = util::unsuffixed(
+            p.address_block
+                .as_ref()
+                .map(|ab| u64::from(ab.size))
+                .unwrap_or(0),
+        );
+        let derived_from = match &p.derived_from {
+            Some(d) => quote!(Some(#d)),
+            None => quote!(None),
+        };
+
+        quote! {
+            PeripheralInfo {
+                name: #name,
+                base_address: #base_address,
+                size: #size,
+                derived_from: #derived_from,
+            }
+        }
+    });
+
+    // Peripherals `derivedFrom` another one commonly re-declare the same
+    // interrupts; keep one entry per interrupt number, same as `interrupt::render`.
+    let mut seen_interrupts = std::collections::HashSet::new();
+    let interrupts = d
+        .peripherals
+        .iter()
+        .flat_map(|p| p.interrupt.iter())
+        .filter(|i| seen_interrupts.insert(i.value))
+        .map(|i| {
+            let name = &i.name;
+            let value = util::unsuffixed(u64::from(i.value));
+
+            quote! {
+                InterruptInfo {
+                    name: #name,
+                    value: #value,
+                }
+            }
+        });
+
+    let (nvic_prio_bits, fpu_present) = match d.cpu.as_ref() {
+        Some(cpu) => (
+            util::unsuffixed(u64::from(cpu.nvic_priority_bits)),
+            cpu.fpu_present,
+        ),
+        None => (util::unsuffixed(0), false),
+    };
+
+    Ok(quote! {
+        ///Machine-readable description of this device
+        ///
+        ///Meant to be consumed from a downstream crate's `build.rs`:
+        ///`include!` this module and iterate over [`METADATA`] to generate
+        ///clock trees, pin maps, or DMA wiring without re-parsing the SVD.
+        pub mod metadata {
+            ///A single peripheral instance
+            #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+            pub struct PeripheralInfo {
+                ///Peripheral name, as it appears in `Peripherals`
+                pub name: &'static str,
+                ///Base address of the peripheral's register block
+                pub base_address: u64,
+                ///Size in bytes of the peripheral's register block
+                pub size: u64,
+                ///Name of the peripheral this one is derived from, if any
+                pub derived_from: Option<&'static str>,
+            }
+
+            ///A single interrupt
+            #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+            pub struct InterruptInfo {
+                ///Interrupt name
+                pub name: &'static str,
+                ///Interrupt number
+                pub value: u64,
+            }
+
+            ///Information about the device's core
+            #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+            pub struct CpuInfo {
+                ///Number of bits available in the NVIC for configuring priority
+                pub nvic_prio_bits: u8,
+                ///Whether the core has an FPU
+                pub fpu_present: bool,
+            }
+
+            ///Description of the whole device
+            #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+            pub struct DeviceMetadata {
+                ///All peripherals present on the device
+                pub peripherals: &'static [PeripheralInfo],
+                ///All interrupts present on the device
+                pub interrupts: &'static [InterruptInfo],
+                ///Information about the device's core
+                pub cpu: CpuInfo,
+            }
+
+            ///Metadata describing this device
+            pub const METADATA: DeviceMetadata = DeviceMetadata {
+                peripherals: &[
+                    #(#peripherals),*
+                ],
+                interrupts: &[
+                    #(#interrupts),*
+                ],
+                cpu: CpuInfo {
+                    nvic_prio_bits: #nvic_prio_bits,
+                    fpu_present: #fpu_present,
+                },
+            };
+        }
+    })
+}